@@ -0,0 +1,132 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! PE/COFF `.sbat` section extraction.
+//!
+//! This module is gated behind the `object` feature. It lets callers
+//! pass a whole UEFI PE image to the revocation/metadata parsers
+//! instead of having to locate the `.sbat` section themselves.
+
+use crate::{Error, Result};
+use object::{Object, ObjectSection};
+
+/// Name of the PE section that stores SBAT CSV data.
+const SBAT_SECTION_NAME: &str = ".sbat";
+
+/// Find the `.sbat` section in a PE/COFF image and return its raw CSV
+/// data with trailing NUL padding removed.
+///
+/// Section data is padded with NUL bytes up to the section's
+/// `SizeOfRawData`, so any trailing NULs are trimmed before the bytes
+/// are handed to [`parse_csv`].
+///
+/// [`parse_csv`]: crate::csv::parse_csv
+pub fn extract_sbat_section(pe_data: &[u8]) -> Result<&[u8]> {
+    let file = object::File::parse(pe_data)
+        .map_err(|_| Error::InvalidPe("failed to parse PE/COFF image"))?;
+
+    let section = file
+        .section_by_name(SBAT_SECTION_NAME)
+        .ok_or(Error::MissingSbatSection)?;
+
+    let data = section
+        .data()
+        .map_err(|_| Error::InvalidPe("failed to read .sbat section data"))?;
+
+    Ok(trim_trailing_nuls(data))
+}
+
+/// Trim trailing NUL bytes used to pad a PE section's raw data.
+fn trim_trailing_nuls(data: &[u8]) -> &[u8] {
+    let end = data.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+    &data[..end]
+}
+
+impl<'a, Storage> crate::metadata::Metadata<'a, Storage>
+where
+    Storage: crate::vec::Veclike<crate::metadata::Entry<'a>>,
+{
+    /// Parse SBAT metadata from the `.sbat` section of a PE/COFF image.
+    ///
+    /// This walks the COFF section table to find the section named
+    /// `.sbat`, trims trailing NUL padding from its raw data, and
+    /// parses the result the same way as `Metadata::parse`.
+    pub fn parse_from_pe(&mut self, pe_data: &'a [u8]) -> Result<()> {
+        let csv = extract_sbat_section(pe_data)?;
+        self.parse(csv)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object::write::Object;
+    use object::{Architecture, BinaryFormat, Endianness, SectionKind};
+
+    #[test]
+    fn trim_trailing_nuls_removes_padding() {
+        assert_eq!(trim_trailing_nuls(b"sbat,1,1\0\0\0\0"), b"sbat,1,1");
+        assert_eq!(trim_trailing_nuls(b"sbat,1,1"), b"sbat,1,1");
+        assert_eq!(trim_trailing_nuls(b"\0\0\0"), b"");
+        assert_eq!(trim_trailing_nuls(b""), b"");
+    }
+
+    /// Build a minimal COFF object with a single section named
+    /// `section_name` containing `data`.
+    ///
+    /// `object::write` can only emit ELF/COFF/Mach-O/XCOFF, not PE, so
+    /// this is a bare COFF object file rather than a real `.efi` PE
+    /// image. `extract_sbat_section` walks the same `Object`/
+    /// `ObjectSection` section-table API for both formats, so this
+    /// still covers that logic, but it does not exercise the PE
+    /// header parsing path a real UEFI image would go through; that
+    /// would need a checked-in `.efi` fixture.
+    fn build_coff(section_name: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut obj = Object::new(
+            BinaryFormat::Coff,
+            Architecture::X86_64,
+            Endianness::Little,
+        );
+        let section = obj.add_section(
+            Vec::new(),
+            section_name.to_vec(),
+            SectionKind::Data,
+        );
+        obj.append_section_data(section, data, 1);
+        obj.write().expect("failed to write synthetic COFF object")
+    }
+
+    #[test]
+    fn extract_sbat_section_reads_and_trims_padding() {
+        let csv = b"sbat,1,2021030218";
+        let mut padded = csv.to_vec();
+        padded.extend_from_slice(&[0, 0, 0, 0]);
+
+        let image = build_coff(SBAT_SECTION_NAME.as_bytes(), &padded);
+
+        assert_eq!(extract_sbat_section(&image).unwrap(), csv);
+    }
+
+    #[test]
+    fn extract_sbat_section_missing_section() {
+        let image = build_coff(b".text", b"not sbat data");
+
+        assert_eq!(
+            extract_sbat_section(&image),
+            Err(Error::MissingSbatSection)
+        );
+    }
+
+    #[test]
+    fn extract_sbat_section_invalid_image() {
+        assert_eq!(
+            extract_sbat_section(b"this is not a PE/COFF image"),
+            Err(Error::InvalidPe("failed to parse PE/COFF image"))
+        );
+    }
+}
@@ -33,6 +33,14 @@ pub enum Error {
 
     /// CSV record has two few fields.
     TooFewFields,
+
+    /// The PE/COFF image does not contain a `.sbat` section.
+    #[cfg(feature = "object")]
+    MissingSbatSection,
+
+    /// The PE/COFF image could not be parsed.
+    #[cfg(feature = "object")]
+    InvalidPe(&'static str),
 }
 
 /// SBAT [`Result`] type alias.
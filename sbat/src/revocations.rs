@@ -33,6 +33,42 @@ pub enum ValidationResult<'r, 'a> {
     Revoked(&'r Entry<'a>),
 }
 
+/// A single problem found while lenient-parsing a CSV record.
+///
+/// Produced by [`Revocations::parse_lenient_with`]/[`parse_lenient`]
+/// instead of aborting the whole parse, so tooling can point at the
+/// offending record.
+///
+/// [`parse_lenient`]: Revocations::parse_lenient
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Diagnostic {
+    /// Index of the record (0-based, counting newline-delimited
+    /// records) the error occurred in.
+    pub record_index: usize,
+    /// Index of the field (0-based) the error is specific to, if the
+    /// error can be localized to a single field.
+    pub field_index: Option<usize>,
+    /// Byte offset of the start of the record within the original
+    /// input.
+    pub byte_offset: usize,
+    /// The underlying error.
+    pub error: Error,
+}
+
+/// A single revoked component found while checking every entry in a
+/// [`Metadata`] against a [`Revocations`] list.
+///
+/// Pairs the offending image [`Entry`] with the revocation
+/// [`Component`] that revoked it, so callers can report both the
+/// image's generation and the minimum generation required to pass.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RevokedEntry<'r, 'c, 'a> {
+    /// The revoked entry from the image's metadata.
+    pub entry: &'r Entry<'a>,
+    /// The revocation component that caused `entry` to be revoked.
+    pub revoked_by: Component<'c>,
+}
+
 /// SBAT revocation data.
 ///
 /// This contains SBAT revocation data parsed from a UEFI variable such
@@ -56,10 +92,7 @@ where
     /// storage. Existing data in `components` is not cleared. The
     /// `date` is set to `None`.
     pub fn new(components: Storage) -> Self {
-        Self {
-            components,
-            date: None,
-        }
+        Self { components, date: None }
     }
 
     /// Parse SBAT data from raw CSV. This data typically comes from a
@@ -86,6 +119,207 @@ where
         })
     }
 
+    /// Parse SBAT CSV data, recovering from malformed records instead
+    /// of aborting on the first error.
+    ///
+    /// Unlike [`parse`], a bad record (invalid generation, disallowed
+    /// character, too few fields, ...) does not stop the parse: the
+    /// record is skipped, a [`Diagnostic`] describing the problem is
+    /// passed to `on_diagnostic`, and parsing continues with the next
+    /// newline-delimited record. This lets signing/build tooling show
+    /// every problem in a revocation payload in one pass.
+    ///
+    /// Any existing data is cleared before parsing.
+    ///
+    /// [`parse`]: Self::parse
+    pub fn parse_lenient_with<F>(
+        &mut self,
+        input: &'a [u8],
+        mut on_diagnostic: F,
+    ) where
+        F: FnMut(Diagnostic),
+    {
+        self.components.clear();
+        self.date = None;
+
+        let mut byte_offset = 0;
+        // Whether the header record (the only one that may carry a
+        // `date` field) has been seen yet. Blank lines are skipped
+        // below without affecting this, so it tracks the first
+        // non-blank record rather than `record_index == 0`.
+        let mut header_seen = false;
+        for (record_index, line) in input.split(|&b| b == b'\n').enumerate() {
+            let record_start = byte_offset;
+            byte_offset += line.len() + 1;
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let first = !header_seen;
+            header_seen = true;
+
+            let result =
+                parse_csv(line, |record: Record<MAX_HEADER_FIELDS>| {
+                    if first {
+                        self.date = record.get_field(2);
+                    }
+
+                    self.components.try_push(Component {
+                        name: record.get_field(0).ok_or(Error::TooFewFields)?,
+                        generation: record
+                            .get_field_as_generation(1)?
+                            .ok_or(Error::TooFewFields)?,
+                    })
+                });
+
+            if let Err(error) = result {
+                // The generation is the only field we can reliably
+                // blame for `InvalidGeneration`/`TooFewFields`; other
+                // errors (e.g. a stray special char) could be in any
+                // field of the record.
+                let field_index = match error {
+                    Error::InvalidGeneration | Error::TooFewFields => Some(1),
+                    _ => None,
+                };
+
+                on_diagnostic(Diagnostic {
+                    record_index,
+                    field_index,
+                    byte_offset: record_start,
+                    error,
+                });
+            }
+        }
+    }
+
+    /// Like [`parse_lenient_with`], but collects diagnostics into a
+    /// `Vec`.
+    ///
+    /// [`parse_lenient_with`]: Self::parse_lenient_with
+    #[cfg(feature = "alloc")]
+    pub fn parse_lenient(
+        &mut self,
+        input: &'a [u8],
+    ) -> alloc::vec::Vec<Diagnostic> {
+        let mut diagnostics = alloc::vec::Vec::new();
+        self.parse_lenient_with(input, |diagnostic| {
+            diagnostics.push(diagnostic)
+        });
+        diagnostics
+    }
+
+    /// Parse SBAT revocation data from the `.sbat` section of a PE/COFF
+    /// image.
+    ///
+    /// This walks the COFF section table to find the section named
+    /// `.sbat`, trims trailing NUL padding from its raw data, and
+    /// parses the result the same way as [`parse`].
+    ///
+    /// [`parse`]: Self::parse
+    #[cfg(feature = "object")]
+    pub fn parse_from_pe(&mut self, pe_data: &'a [u8]) -> Result<()> {
+        let csv = crate::pe::extract_sbat_section(pe_data)?;
+        self.parse(csv)
+    }
+
+    /// Parse SBAT data from raw CSV and append it to the existing
+    /// revocation data, without clearing it first.
+    ///
+    /// This is a lower-level building block for combining multiple
+    /// sources of revocation data; see also [`merge`], which
+    /// additionally keeps only the higher generation when the same
+    /// component appears in both sources.
+    ///
+    /// [`merge`]: Self::merge
+    pub fn parse_additive(&mut self, input: &'a [u8]) -> Result<()> {
+        let mut first = true;
+
+        parse_csv(input, |record: Record<MAX_HEADER_FIELDS>| {
+            if first {
+                if let Some(date) = record.get_field(2) {
+                    self.date = Some(date);
+                }
+                first = false;
+            }
+
+            self.components.try_push(Component {
+                name: record.get_field(0).ok_or(Error::TooFewFields)?,
+                generation: record
+                    .get_field_as_generation(1)?
+                    .ok_or(Error::TooFewFields)?,
+            })
+        })
+    }
+
+    /// Merge another set of revocation data into this one.
+    ///
+    /// Each component in `self` has its generation raised to the
+    /// highest generation `other` has for the same name, and each
+    /// component of `other` whose name is not in `self` is appended.
+    /// When neither source repeats a name, the result has exactly one
+    /// entry per component name, with the *higher* of the two
+    /// generations (so the effective policy used by
+    /// [`is_component_revoked`] and [`validate_metadata_all_with`] is
+    /// the per-component maximum generation across both sources, not
+    /// just an approximation of it). Entries that repeat a name within
+    /// `self` or within `other` are not collapsed into one. The `date`
+    /// is set to the later of the two dates.
+    ///
+    /// This lets callers combine layered revocation data -- such as a
+    /// baseline compiled into the bootloader plus the `SbatLevel` UEFI
+    /// variable -- before running [`validate_metadata`].
+    ///
+    /// [`is_component_revoked`]: Self::is_component_revoked
+    /// [`validate_metadata_all_with`]: Self::validate_metadata_all_with
+    /// [`validate_metadata`]: Self::validate_metadata
+    pub fn merge<OtherStorage>(
+        &mut self,
+        other: &Revocations<'a, OtherStorage>,
+    ) -> Result<()>
+    where
+        OtherStorage: Veclike<Component<'a>>,
+        Storage: Default,
+    {
+        let mut merged = Storage::default();
+
+        // Existing components, with their generation raised to
+        // `other`'s if `other` has a higher one for the same name.
+        for existing in self.components.as_slice() {
+            let mut component = existing.clone();
+            for incoming in other.revoked_components() {
+                if incoming.name == component.name
+                    && incoming.generation > component.generation
+                {
+                    component.generation = incoming.generation.clone();
+                }
+            }
+            merged.try_push(component)?;
+        }
+
+        // Components that only `other` has.
+        for incoming in other.revoked_components() {
+            let already_present = self
+                .components
+                .as_slice()
+                .iter()
+                .any(|existing| existing.name == incoming.name);
+            if !already_present {
+                merged.try_push(incoming.clone())?;
+            }
+        }
+
+        self.components = merged;
+
+        self.date = match (self.date, other.date) {
+            (Some(a), Some(b)) if b.as_str() > a.as_str() => Some(b),
+            (None, Some(b)) => Some(b),
+            (a, _) => a,
+        };
+
+        Ok(())
+    }
+
     /// Date when the data was last updated. This is optional metadata
     /// in the first entry and may not be present.
     pub fn date(&self) -> &Option<&AsciiStr> {
@@ -102,7 +336,15 @@ where
     /// allowed.
     #[must_use]
     pub fn is_component_revoked(&self, input: &Component) -> bool {
-        self.components.as_slice().iter().any(|revoked_component| {
+        self.find_revoking_component(input).is_some()
+    }
+
+    /// Find the revocation [`Component`] that revokes `input`, if any.
+    fn find_revoking_component(
+        &self,
+        input: &Component,
+    ) -> Option<&Component<'a>> {
+        self.components.as_slice().iter().find(|revoked_component| {
             input.name == revoked_component.name
                 && input.generation < revoked_component.generation
         })
@@ -134,12 +376,75 @@ where
         }
     }
 
+    /// Check every component in `metadata` against the revocation list,
+    /// invoking `f` for each revoked entry found.
+    ///
+    /// Unlike [`validate_metadata`], which stops at the first revoked
+    /// entry, this visits every entry in `metadata`, so callers can
+    /// report the full set of problems in one pass. `f` is called with
+    /// the revoked entry and the revocation [`Component`] that revoked
+    /// it, so both the image generation and the minimum allowed
+    /// generation are available.
+    ///
+    /// [`validate_metadata`]: Self::validate_metadata
+    pub fn validate_metadata_all_with<'r, 'b, MetadataStorage, F>(
+        &self,
+        metadata: &'r Metadata<'b, MetadataStorage>,
+        mut f: F,
+    ) where
+        MetadataStorage: Veclike<Entry<'b>>,
+        F: FnMut(&'r Entry<'b>, Component<'a>),
+    {
+        for entry in metadata.entries() {
+            if let Some(revoked_by) =
+                self.find_revoking_component(&entry.component)
+            {
+                f(entry, revoked_by.clone());
+            }
+        }
+    }
+
+    /// Like [`validate_metadata_all_with`], but collects every revoked
+    /// entry into a `Vec`.
+    ///
+    /// [`validate_metadata_all_with`]: Self::validate_metadata_all_with
+    #[cfg(feature = "alloc")]
+    pub fn validate_metadata_all<'r, 'b, MetadataStorage>(
+        &self,
+        metadata: &'r Metadata<'b, MetadataStorage>,
+    ) -> alloc::vec::Vec<RevokedEntry<'r, 'a, 'b>>
+    where
+        MetadataStorage: Veclike<Entry<'b>>,
+    {
+        let mut revoked = alloc::vec::Vec::new();
+        self.validate_metadata_all_with(metadata, |entry, revoked_by| {
+            revoked.push(RevokedEntry { entry, revoked_by });
+        });
+        revoked
+    }
+
     /// Get the revoked components as a slice. The component version
     /// indicates the lowest *allowed* version of this component; all
     /// lower versions are considered revoked.
     pub fn revoked_components(&self) -> &[Component<'a>] {
         self.components.as_slice()
     }
+
+    /// Construct a `Revocations` directly from its parts.
+    ///
+    /// This is a crate-internal escape hatch for code (such as the
+    /// `serde` support in [`crate::serde_impl`]) that needs to build a
+    /// `Revocations` without going through [`parse`]/[`merge`].
+    ///
+    /// [`parse`]: Self::parse
+    /// [`merge`]: Self::merge
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_parts(
+        date: Option<&'a AsciiStr>,
+        components: Storage,
+    ) -> Self {
+        Self { date, components }
+    }
 }
 
 #[cfg(test)]
@@ -313,6 +618,186 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_lenient_with_recovers_from_bad_records() {
+        // The second record is missing a generation, and the fourth
+        // has a malformed generation; both should be skipped while the
+        // rest of the records still parse.
+        let input = b"sbat,1,2021030218\ncompA\ncompB,2\ncompC,x\ncompD,4";
+
+        let array = ArrayVec::<_, 3>::new();
+        let mut revocations = Revocations::new(array);
+
+        let mut diagnostics = ArrayVec::<_, 2>::new();
+        revocations.parse_lenient_with(input, |diagnostic| {
+            diagnostics.push(diagnostic)
+        });
+
+        assert_eq!(
+            revocations.revoked_components(),
+            [
+                make_component("sbat", 1),
+                make_component("compB", 2),
+                make_component("compD", 4),
+            ]
+        );
+
+        assert_eq!(
+            diagnostics.as_slice(),
+            [
+                Diagnostic {
+                    record_index: 1,
+                    field_index: Some(1),
+                    byte_offset: 18,
+                    error: Error::TooFewFields,
+                },
+                Diagnostic {
+                    record_index: 3,
+                    field_index: Some(1),
+                    byte_offset: 32,
+                    error: Error::InvalidGeneration,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_lenient_with_leading_blank_line_still_finds_header() {
+        // A leading blank line must not be mistaken for the header
+        // record: the header (with its `date` field) is the first
+        // *non-blank* line.
+        let input = b"\nsbat,1,2021030218\ncompA,1";
+
+        let array = ArrayVec::<_, 2>::new();
+        let mut revocations = Revocations::new(array);
+
+        let mut diagnostics = ArrayVec::<_, 1>::new();
+        revocations.parse_lenient_with(input, |diagnostic| {
+            diagnostics.push(diagnostic)
+        });
+
+        assert!(diagnostics.is_empty());
+        assert_eq!(revocations.date(), &Some(ascii("2021030218")));
+        assert_eq!(
+            revocations.revoked_components(),
+            [make_component("sbat", 1), make_component("compA", 1)]
+        );
+    }
+
+    #[test]
+    fn validate_metadata_all_with() {
+        let revocations = make_revocations(&[
+            make_component("compA", 2),
+            make_component("compB", 3),
+        ]);
+
+        let metadata = make_metadata(&[
+            make_component("compA", 1),
+            make_component("compB", 3),
+            make_component("compC", 1),
+            make_component("compB", 1),
+        ]);
+
+        let mut revoked = ArrayVec::<_, 4>::new();
+        revocations.validate_metadata_all_with(
+            &metadata,
+            |entry, revoked_by| {
+                revoked.push((entry.clone(), revoked_by));
+            },
+        );
+
+        assert_eq!(
+            revoked.as_slice(),
+            [
+                (make_entry("compA", 1), make_component("compA", 2)),
+                (make_entry("compB", 1), make_component("compB", 3)),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_additive_appends_without_clearing() {
+        let array = ArrayVec::<_, 4>::new();
+        let mut revocations = Revocations::new(array);
+        revocations.parse(b"sbat,1,2021030218\ncompA,1").unwrap();
+
+        revocations.parse_additive(b"compB,2").unwrap();
+
+        assert_eq!(revocations.date(), &Some(ascii("2021030218")));
+        assert_eq!(
+            revocations.revoked_components(),
+            [
+                make_component("sbat", 1),
+                make_component("compA", 1),
+                make_component("compB", 2)
+            ],
+        );
+    }
+
+    #[test]
+    fn merge_keeps_higher_generation_and_later_date() {
+        let mut revocations = make_revocations(&[
+            make_component("compA", 2),
+            make_component("compB", 5),
+        ]);
+        revocations.date = Some(ascii("2021030218"));
+
+        let mut other = make_revocations(&[
+            make_component("compA", 4),
+            make_component("compB", 1),
+            make_component("compC", 1),
+        ]);
+        other.date = Some(ascii("2022010100"));
+
+        revocations.merge(&other).unwrap();
+
+        assert_eq!(revocations.date(), &Some(ascii("2022010100")));
+
+        // compA: other's higher generation (4) should now be enforced,
+        // including at generation 1, which is below *both* of the
+        // merged-in thresholds -- not just the lower one (2).
+        assert!(revocations.is_component_revoked(&make_component("compA", 1)));
+        assert!(revocations.is_component_revoked(&make_component("compA", 3)));
+        assert!(!revocations.is_component_revoked(&make_component("compA", 4)));
+
+        // compB: self's higher generation (5) should still be enforced.
+        assert!(revocations.is_component_revoked(&make_component("compB", 4)));
+        assert!(!revocations.is_component_revoked(&make_component("compB", 5)));
+
+        // compC: only present in `other`, should be added. Its
+        // revocation threshold is generation 1, so generation 1 itself
+        // is allowed.
+        assert!(!revocations.is_component_revoked(&make_component("compC", 1)));
+
+        // There should be exactly one merged entry per component name,
+        // each carrying the higher of the two generations -- not a
+        // stale lower-generation entry left behind alongside it.
+        assert_eq!(
+            revocations.revoked_components(),
+            [
+                make_component("compA", 4),
+                make_component("compB", 5),
+                make_component("compC", 1),
+            ]
+        );
+
+        // A stale lower-generation entry would also make
+        // `validate_metadata_all_with` report the wrong `revoked_by`
+        // for a generation between the two merged values.
+        let metadata = make_metadata(&[make_component("compA", 3)]);
+        let mut revoked = ArrayVec::<_, 1>::new();
+        revocations.validate_metadata_all_with(
+            &metadata,
+            |entry, revoked_by| {
+                revoked.push((entry.clone(), revoked_by));
+            },
+        );
+        assert_eq!(
+            revoked.as_slice(),
+            [(make_entry("compA", 3), make_component("compA", 4))]
+        );
+    }
+
     /// Test that `Revocations::new` does not clear the storage, and test
     /// that `Revocations::parse` does clear the storage.
     #[test]
@@ -329,4 +814,35 @@ mod tests {
         revocations.parse(b"").unwrap();
         assert!(revocations.revoked_components().is_empty());
     }
+
+    #[cfg(feature = "object")]
+    #[test]
+    fn parse_from_pe_reads_sbat_section() {
+        use object::write::Object;
+        use object::{Architecture, BinaryFormat, Endianness, SectionKind};
+
+        let csv = b"sbat,1,2021030218\ncompA,1";
+        let mut padded = csv.to_vec();
+        padded.extend_from_slice(&[0, 0, 0, 0]);
+
+        let mut obj = Object::new(
+            BinaryFormat::Coff,
+            Architecture::X86_64,
+            Endianness::Little,
+        );
+        let section =
+            obj.add_section(Vec::new(), b".sbat".to_vec(), SectionKind::Data);
+        obj.append_section_data(section, &padded, 1);
+        let image = obj.write().expect("failed to write synthetic COFF object");
+
+        let array = ArrayVec::<_, 2>::new();
+        let mut revocations = Revocations::new(array);
+        revocations.parse_from_pe(&image).unwrap();
+
+        assert_eq!(revocations.date(), &Some(ascii("2021030218")));
+        assert_eq!(
+            revocations.revoked_components(),
+            [make_component("sbat", 1), make_component("compA", 1)]
+        );
+    }
 }
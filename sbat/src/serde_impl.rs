@@ -0,0 +1,269 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Optional `serde` support, gated behind the `serde` feature.
+//!
+//! `AsciiStr` fields are serialized as plain strings, and
+//! deserializing re-validates them as ASCII (and re-validates
+//! [`Generation`] values), so a round-tripped value can't bypass the
+//! checks that CSV parsing already enforces. The `serde` feature
+//! depends on `alloc`.
+//!
+//! [`Generation`]: crate::Generation
+
+use crate::metadata::{Entry, Metadata};
+use crate::revocations::Revocations;
+use crate::vec::Veclike;
+use crate::{Component, Error, Generation};
+use alloc::vec::Vec;
+use ascii::AsciiStr;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+impl Serialize for Generation {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.get().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Generation {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        let value = u32::deserialize(deserializer)?;
+        Generation::new(value).ok_or_else(|| {
+            de::Error::custom(alloc::format!("{:?}", Error::InvalidGeneration))
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Component")]
+struct ComponentShadow<'a> {
+    name: &'a str,
+    generation: Generation,
+}
+
+impl<'a> Serialize for Component<'a> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        ComponentShadow {
+            name: self.name.as_str(),
+            generation: self.generation.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for Component<'a> {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        let shadow = ComponentShadow::deserialize(deserializer)?;
+        let name = AsciiStr::from_ascii(shadow.name).map_err(|_| {
+            de::Error::custom(alloc::format!("{:?}", Error::InvalidAscii))
+        })?;
+        Ok(Component { name, generation: shadow.generation })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Entry")]
+struct EntryShadow<'a> {
+    component: Component<'a>,
+    vendor: crate::metadata::Vendor,
+}
+
+impl<'a> Serialize for Entry<'a> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("Entry", 2)?;
+        state.serialize_field("component", &self.component)?;
+        state.serialize_field("vendor", &self.vendor)?;
+        state.end()
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for Entry<'a> {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        let shadow = EntryShadow::deserialize(deserializer)?;
+        Ok(Entry::new(shadow.component, shadow.vendor))
+    }
+}
+
+impl<'a, Storage> Serialize for Metadata<'a, Storage>
+where
+    Storage: Veclike<Entry<'a>>,
+{
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.entries().serialize(serializer)
+    }
+}
+
+impl<'de: 'a, 'a, Storage> Deserialize<'de> for Metadata<'a, Storage>
+where
+    Storage: Veclike<Entry<'a>> + Default,
+{
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        let entries = Vec::<Entry<'a>>::deserialize(deserializer)?;
+        let mut storage = Storage::default();
+        for entry in entries {
+            storage.try_push(entry).map_err(|_| {
+                de::Error::custom(alloc::format!("{:?}", Error::TooManyRecords))
+            })?;
+        }
+        Ok(Metadata::new(storage))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Revocations")]
+struct RevocationsShadow<'a> {
+    date: Option<&'a str>,
+    components: Vec<Component<'a>>,
+}
+
+impl<'a, Storage> Serialize for Revocations<'a, Storage>
+where
+    Storage: Veclike<Component<'a>>,
+{
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("Revocations", 2)?;
+        state.serialize_field("date", &self.date().map(AsciiStr::as_str))?;
+        state.serialize_field("components", self.revoked_components())?;
+        state.end()
+    }
+}
+
+impl<'de: 'a, 'a, Storage> Deserialize<'de> for Revocations<'a, Storage>
+where
+    Storage: Veclike<Component<'a>> + Default,
+{
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        let shadow = RevocationsShadow::deserialize(deserializer)?;
+
+        let date = shadow.date.map(AsciiStr::from_ascii).transpose().map_err(
+            |_| de::Error::custom(alloc::format!("{:?}", Error::InvalidAscii)),
+        )?;
+
+        let mut components = Storage::default();
+        for component in shadow.components {
+            components.try_push(component).map_err(|_| {
+                de::Error::custom(alloc::format!("{:?}", Error::TooManyRecords))
+            })?;
+        }
+
+        Ok(Revocations::from_parts(date, components))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::Vendor;
+    use crate::revocations::Revocations;
+    use arrayvec::ArrayVec;
+
+    fn ascii(s: &str) -> &ascii::AsciiStr {
+        ascii::AsciiStr::from_ascii(s).unwrap()
+    }
+
+    #[test]
+    fn component_round_trips_through_json() {
+        let component =
+            Component::new(ascii("compA"), Generation::new(2).unwrap());
+        let json = serde_json::to_string(&component).unwrap();
+        assert_eq!(json, r#"{"name":"compA","generation":2}"#);
+
+        let round_tripped: Component = serde_json::from_str(&json).unwrap();
+        assert_eq!(round_tripped, component);
+    }
+
+    #[test]
+    fn component_rejects_non_ascii_name_on_deserialize() {
+        let json = r#"{"name":"café","generation":2}"#;
+        assert!(serde_json::from_str::<Component>(json).is_err());
+    }
+
+    #[test]
+    fn generation_rejects_out_of_range_value_on_deserialize() {
+        // `0` is not a valid generation: the lowest allowed generation
+        // is 1, matching CSV parsing's rejection of the same value.
+        assert!(serde_json::from_str::<Generation>("0").is_err());
+    }
+
+    #[test]
+    fn entry_round_trips_through_json() {
+        let entry = Entry::new(
+            Component::new(ascii("compA"), Generation::new(2).unwrap()),
+            Vendor::default(),
+        );
+
+        let json = serde_json::to_string(&entry).unwrap();
+        let round_tripped: Entry = serde_json::from_str(&json).unwrap();
+        assert_eq!(round_tripped, entry);
+    }
+
+    #[test]
+    fn metadata_round_trips_through_json() {
+        let mut entries = ArrayVec::<Entry, 2>::new();
+        entries.push(Entry::new(
+            Component::new(ascii("compA"), Generation::new(2).unwrap()),
+            Vendor::default(),
+        ));
+        entries.push(Entry::new(
+            Component::new(ascii("compB"), Generation::new(3).unwrap()),
+            Vendor::default(),
+        ));
+        let metadata = Metadata::new(entries);
+
+        let json = serde_json::to_string(&metadata).unwrap();
+        let round_tripped: Metadata<'_, ArrayVec<Entry<'_>, 2>> =
+            serde_json::from_str(&json).unwrap();
+        assert_eq!(round_tripped.entries(), metadata.entries());
+    }
+
+    #[test]
+    fn revocations_round_trips_through_json() {
+        let mut components = ArrayVec::<Component, 2>::new();
+        components
+            .push(Component::new(ascii("compA"), Generation::new(2).unwrap()));
+        components
+            .push(Component::new(ascii("compB"), Generation::new(3).unwrap()));
+        let revocations = Revocations::new(components);
+
+        let json = serde_json::to_string(&revocations).unwrap();
+        let round_tripped: Revocations<'_, ArrayVec<Component<'_>, 2>> =
+            serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            round_tripped.revoked_components(),
+            revocations.revoked_components()
+        );
+        assert_eq!(round_tripped.date(), revocations.date());
+    }
+}